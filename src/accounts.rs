@@ -0,0 +1,170 @@
+//! Deploys and tracks a pool of "burner" accounts so the load engine can
+//! submit from many independent nonce streams instead of serializing every
+//! transaction behind one account.
+
+use std::sync::Arc;
+
+use color_eyre::eyre::Result;
+use log::info;
+use starknet::{
+    accounts::{
+        Account, AccountFactory, Call, ConnectedAccount, ExecutionEncoding,
+        OpenZeppelinAccountFactory, SingleOwnerAccount,
+    },
+    core::{
+        types::FieldElement,
+        utils::{get_udc_deployed_address, UdcUniqueness},
+    },
+    macros::selector,
+    providers::jsonrpc::{HttpTransport, JsonRpcClient},
+    signers::{LocalWallet, SigningKey},
+};
+
+use crate::{
+    confirm::{self, BackoffConfig},
+    MAX_FEE,
+};
+
+/// Canonical address of the ETH fee token on Starknet, used to pay
+/// transaction fees regardless of which contract a transaction calls.
+const FEE_TOKEN_ADDRESS: FieldElement =
+    starknet::macros::felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+
+/// Fee-token amount transferred to each burner, enough to cover its own
+/// deploy-account transaction plus the invokes it will submit.
+const FEE_TOKEN_FUND_AMOUNT: FieldElement = starknet::macros::felt!("0x16345785d8a0000");
+
+/// A single funded, deployed burner account ready to sign and submit its
+/// own transactions.
+pub struct BurnerAccount {
+    pub account: SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+}
+
+/// Deploys `count` burner accounts: each gets a fresh signing key, is
+/// funded from `master` in both the fee token (to pay for its own
+/// deploy-account transaction and later invokes) and the ERC20 under test
+/// (to have a balance to transfer in the load flow), and is
+/// deploy-account'd on chain before being handed back ready to drive its
+/// own load.
+pub async fn deploy_burner_accounts(
+    starknet_rpc: Arc<JsonRpcClient<HttpTransport>>,
+    master: &mut SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+    erc20_address: FieldElement,
+    account_class_hash: FieldElement,
+    chain_id: FieldElement,
+    count: usize,
+    fund_amount: FieldElement,
+) -> Result<Vec<BurnerAccount>> {
+    let mut burners = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let signing_key = SigningKey::from_random();
+        let salt = signing_key.secret_scalar();
+        let constructor_calldata = vec![signing_key.verifying_key().scalar()];
+
+        let address = get_udc_deployed_address(
+            salt,
+            account_class_hash,
+            &UdcUniqueness::NotUnique,
+            &constructor_calldata,
+        );
+
+        info!("Burner {i}: funding and deploying at {address:#064x}");
+
+        transfer_token(
+            starknet_rpc.clone(),
+            master,
+            FEE_TOKEN_ADDRESS,
+            address,
+            FEE_TOKEN_FUND_AMOUNT,
+        )
+        .await?;
+        transfer_token(starknet_rpc.clone(), master, erc20_address, address, fund_amount).await?;
+
+        let signer = LocalWallet::from(signing_key);
+
+        deploy_burner_account(
+            starknet_rpc.clone(),
+            signer.clone(),
+            account_class_hash,
+            chain_id,
+            salt,
+        )
+        .await?;
+
+        let account = SingleOwnerAccount::new(
+            starknet_rpc.clone(),
+            signer,
+            address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+
+        burners.push(BurnerAccount { account });
+    }
+
+    Ok(burners)
+}
+
+/// Transfers `amount` of `token` from `master` to `to`. Used both to fund
+/// a burner's fee-token balance (so it can pay for its own transactions)
+/// and its ERC20-under-test balance (so it has something to transfer in
+/// the load flow).
+async fn transfer_token(
+    starknet_rpc: Arc<JsonRpcClient<HttpTransport>>,
+    master: &mut SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+    token: FieldElement,
+    to: FieldElement,
+    amount: FieldElement,
+) -> Result<()> {
+    let nonce = master.get_nonce().await?;
+
+    let call = Call {
+        to: token,
+        selector: selector!("transfer"),
+        calldata: vec![to, amount, FieldElement::ZERO],
+    };
+
+    let result = master
+        .execute(vec![call])
+        .max_fee(MAX_FEE)
+        .nonce(nonce)
+        .send()
+        .await?;
+
+    confirm::wait_for_tx(
+        &starknet_rpc,
+        result.transaction_hash,
+        BackoffConfig::default(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Sends the deploy-account transaction for a burner and waits for it to
+/// be accepted.
+async fn deploy_burner_account(
+    starknet_rpc: Arc<JsonRpcClient<HttpTransport>>,
+    signer: LocalWallet,
+    class_hash: FieldElement,
+    chain_id: FieldElement,
+    salt: FieldElement,
+) -> Result<()> {
+    let factory =
+        OpenZeppelinAccountFactory::new(class_hash, chain_id, signer, starknet_rpc.clone())
+            .await?;
+
+    let result = factory.deploy(salt).max_fee(MAX_FEE).send().await?;
+
+    confirm::wait_for_tx(
+        &starknet_rpc,
+        result.transaction_hash,
+        BackoffConfig::default(),
+    )
+    .await?;
+
+    info!("Deploy-account transaction {:#064x} accepted", result.transaction_hash);
+
+    Ok(())
+}
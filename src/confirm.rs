@@ -0,0 +1,169 @@
+//! Transaction confirmation: polls the provider for a receipt until it
+//! reaches a terminal state, with exponential backoff + jitter between
+//! polls and bounded retries on transient transport errors. Also exposes a
+//! batched variant that confirms many transactions concurrently.
+
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::{bail, eyre};
+use futures::stream::{self, StreamExt};
+use log::debug;
+use rand::Rng;
+use starknet::{
+    core::types::{
+        ExecutionResult, FieldElement, MaybePendingTransactionReceipt, StarknetError,
+    },
+    providers::{
+        jsonrpc::{HttpTransport, JsonRpcClient},
+        MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage,
+    },
+};
+
+/// Backoff schedule for polling a pending transaction, and retry budget
+/// for transient transport errors.
+#[derive(Debug, Clone, Copy)]
+pub struct BackoffConfig {
+    pub initial_interval: Duration,
+    pub max_interval: Duration,
+    pub multiplier: f64,
+    pub max_transport_retries: u32,
+    pub timeout: Duration,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_millis(250),
+            max_interval: Duration::from_secs(5),
+            multiplier: 2.0,
+            max_transport_retries: 5,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl BackoffConfig {
+    /// Multiplies by `multiplier` (capped at `max_interval`) and adds up to
+    /// 20% jitter.
+    fn next_interval(&self, current: Duration) -> Duration {
+        let doubled = current.mul_f64(self.multiplier).min(self.max_interval);
+        let jitter_frac = rand::thread_rng().gen_range(0.0..0.2);
+        doubled.mul_f64(1.0 + jitter_frac)
+    }
+}
+
+/// The outcome of waiting for a transaction: its terminal execution result,
+/// and the instant it was actually observed to reach that state (not the
+/// instant the caller got around to looking at the result).
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    pub result: ExecutionResult,
+    pub accepted_at: Instant,
+}
+
+/// Waits for `tx_hash` to show up and reach a terminal state, polling with
+/// exponential backoff. Transport errors are retried up to
+/// `backoff.max_transport_retries` times before giving up.
+pub async fn wait_for_tx(
+    provider: &JsonRpcClient<HttpTransport>,
+    tx_hash: FieldElement,
+    backoff: BackoffConfig,
+) -> color_eyre::Result<Confirmation> {
+    let start = Instant::now();
+    let mut interval = backoff.initial_interval;
+    let mut transport_retries = 0;
+
+    loop {
+        if start.elapsed() >= backoff.timeout {
+            bail!("Timeout while waiting for transaction {tx_hash:#064x}");
+        }
+
+        match provider.get_transaction_receipt(tx_hash).await {
+            Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => {
+                return Ok(Confirmation {
+                    result: receipt.execution_result().clone(),
+                    accepted_at: Instant::now(),
+                });
+            }
+            Ok(MaybePendingTransactionReceipt::PendingReceipt(pending)) => {
+                if let result @ ExecutionResult::Reverted { .. } = pending.execution_result() {
+                    return Ok(Confirmation {
+                        result: result.clone(),
+                        accepted_at: Instant::now(),
+                    });
+                }
+                debug!("Waiting for transaction {tx_hash:#064x} to be accepted, next poll in {interval:?}");
+                tokio::time::sleep(interval).await;
+                interval = backoff.next_interval(interval);
+            }
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
+                ..
+            })) => {
+                debug!("Waiting for transaction {tx_hash:#064x} to show up, next poll in {interval:?}");
+                tokio::time::sleep(interval).await;
+                interval = backoff.next_interval(interval);
+            }
+            Err(err) if transport_retries < backoff.max_transport_retries => {
+                transport_retries += 1;
+                debug!(
+                    "Transient error polling transaction {tx_hash:#064x} ({err}), retry {transport_retries}/{}",
+                    backoff.max_transport_retries
+                );
+                tokio::time::sleep(interval).await;
+                interval = backoff.next_interval(interval);
+            }
+            Err(err) => {
+                return Err(eyre!(err).wrap_err(format!(
+                    "Error while waiting for transaction {tx_hash:#064x}"
+                )))
+            }
+        }
+    }
+}
+
+/// The outcome of waiting for one transaction in a batch: either it
+/// reached a terminal state, or confirmation itself gave up on it
+/// (timeout, or transport retries exhausted).
+#[derive(Debug, Clone)]
+pub enum ConfirmationOutcome {
+    Reached(Confirmation),
+    Failed { tx_hash: FieldElement, error: String },
+}
+
+/// Confirms many transaction hashes concurrently, bounding in-flight
+/// confirmations to `concurrency` so the polling phase scales with the
+/// submission phase instead of confirming strictly one at a time.
+///
+/// Unlike [`wait_for_tx`], a single transaction that fails to confirm
+/// does not fail the whole batch: it comes back as
+/// [`ConfirmationOutcome::Failed`] alongside everyone else's results, so
+/// one slow or broken transaction in a load run can't discard the
+/// metrics for the rest of it. Results are returned in the same order as
+/// `tx_hashes`, not completion order.
+pub async fn wait_for_many(
+    provider: &JsonRpcClient<HttpTransport>,
+    tx_hashes: Vec<FieldElement>,
+    backoff: BackoffConfig,
+    concurrency: usize,
+) -> Vec<ConfirmationOutcome> {
+    let mut indexed: Vec<(usize, ConfirmationOutcome)> =
+        stream::iter(tx_hashes.into_iter().enumerate())
+            .map(|(index, tx_hash)| async move {
+                let outcome = match wait_for_tx(provider, tx_hash, backoff).await {
+                    Ok(confirmation) => ConfirmationOutcome::Reached(confirmation),
+                    Err(err) => ConfirmationOutcome::Failed {
+                        tx_hash,
+                        error: err.to_string(),
+                    },
+                };
+                (index, outcome)
+            })
+            .buffer_unordered(concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+    indexed.sort_unstable_by_key(|(index, _)| *index);
+
+    indexed.into_iter().map(|(_, outcome)| outcome).collect()
+}
@@ -0,0 +1,108 @@
+//! A small abstraction over the two contract-class formats Madara accepts,
+//! so the harness can declare either a Cairo 0 (legacy) or Cairo 1 (Sierra)
+//! artifact through the same call site.
+
+use std::sync::Arc;
+
+use color_eyre::eyre::Result;
+use starknet::{
+    accounts::{Account, DeclarationV2, SingleOwnerAccount},
+    core::types::{
+        contract::{legacy::LegacyContractClass, CompiledClass, SierraClass},
+        FieldElement,
+    },
+    providers::jsonrpc::{HttpTransport, JsonRpcClient},
+    signers::LocalWallet,
+};
+
+/// A contract artifact to declare, in whichever format it was authored in.
+pub enum ContractArtifact {
+    /// A Cairo 0 artifact, declared via `declare_legacy`.
+    Legacy(LegacyContractClass),
+    /// A Cairo 1 artifact: the Sierra class plus its compiled CASM class,
+    /// declared via `declare_v2`.
+    Sierra {
+        sierra: SierraClass,
+        compiled: CompiledClass,
+    },
+}
+
+impl ContractArtifact {
+    /// Loads an artifact from `path`, detecting legacy vs. Sierra by
+    /// whether the JSON carries a `sierra_program` field.
+    ///
+    /// For a Sierra artifact, `casm_path` must point at the matching
+    /// compiled CASM class.
+    pub fn load(path: &str, casm_path: Option<&str>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&raw)?;
+
+        if value.get("sierra_program").is_some() {
+            let sierra: SierraClass = serde_json::from_value(value)?;
+            let casm_path = casm_path
+                .ok_or_else(|| color_eyre::eyre::eyre!("Sierra artifact {path} needs a CASM path"))?;
+            let compiled: CompiledClass =
+                serde_json::from_str(&std::fs::read_to_string(casm_path)?)?;
+
+            Ok(Self::Sierra { sierra, compiled })
+        } else {
+            Ok(Self::Legacy(serde_json::from_value(value)?))
+        }
+    }
+
+    /// The class hash of this artifact, regardless of format.
+    pub fn class_hash(&self) -> Result<FieldElement> {
+        match self {
+            Self::Legacy(class) => Ok(class.class_hash()?),
+            Self::Sierra { sierra, .. } => Ok(sierra.class_hash()?),
+        }
+    }
+
+    /// Declares this artifact on-chain, routing to `declare_legacy` or
+    /// `declare_v2` as appropriate.
+    pub async fn declare(
+        self,
+        account: &SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+        nonce: FieldElement,
+        max_fee: FieldElement,
+    ) -> Result<DeclareOutcome> {
+        match self {
+            Self::Legacy(class) => {
+                let tx_resp = account
+                    .declare_legacy(Arc::new(class))
+                    .max_fee(max_fee)
+                    .nonce(nonce)
+                    .send()
+                    .await?;
+
+                Ok(DeclareOutcome {
+                    transaction_hash: tx_resp.transaction_hash,
+                    class_hash: tx_resp.class_hash,
+                })
+            }
+            Self::Sierra { sierra, compiled } => {
+                let flattened = sierra.flatten()?;
+                let compiled_class_hash = compiled.class_hash()?;
+
+                let declaration: DeclarationV2<_> = account
+                    .declare_v2(Arc::new(flattened), compiled_class_hash)
+                    .max_fee(max_fee)
+                    .nonce(nonce);
+
+                let tx_resp = declaration.send().await?;
+
+                Ok(DeclareOutcome {
+                    transaction_hash: tx_resp.transaction_hash,
+                    class_hash: tx_resp.class_hash,
+                })
+            }
+        }
+    }
+}
+
+/// The result of declaring a [`ContractArtifact`], unified across legacy
+/// and Sierra so callers don't need to match on the variant.
+pub struct DeclareOutcome {
+    pub transaction_hash: FieldElement,
+    pub class_hash: FieldElement,
+}
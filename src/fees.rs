@@ -0,0 +1,134 @@
+//! Fee estimation for the load flow's invoke transactions, in place of the
+//! fixed `MAX_FEE` magic constant. The setup transactions (declare, deploy,
+//! burner funding and deploy-account) still pay the static `MAX_FEE`.
+//!
+//! Calls the provider's `estimate_fee` for a transaction and derives
+//! `max_fee` from the estimate with a configurable multiplier. The
+//! estimate's `overall_fee` already prices in data availability post
+//! EIP-4844, so L1 gas price history (tracked the way Madara's
+//! l1-gas-price worker does) is used only as a floor under the estimate
+//! rather than an addend, guarding against gas prices moving between
+//! estimation and inclusion. Falls back to a static fee when estimation
+//! is unavailable.
+
+// `FeeEstimate::{gas_consumed, data_gas_consumed}` and
+// `BlockWithTxHashes::l1_data_gas_price` both require a `starknet-rs`
+// release with v3/blob fee support (0.9+); this crate has no pinned
+// manifest in this checkout, so building against an older `starknet-rs`
+// will fail to compile this file — pin accordingly when the manifest is
+// added.
+use color_eyre::eyre::Result;
+use log::warn;
+use starknet::{
+    accounts::{Account, Call, ExecutionV1, SingleOwnerAccount},
+    core::types::{BlockId, BlockTag, FieldElement},
+    providers::jsonrpc::{HttpTransport, JsonRpcClient},
+    signers::LocalWallet,
+};
+
+use crate::MAX_FEE;
+
+/// Multiplier applied to the raw fee estimate to absorb gas-price drift
+/// between estimation and inclusion.
+const FEE_ESTIMATE_MULTIPLIER: f64 = 1.5;
+
+/// L1 gas prices as of the latest block, mirroring the shape Madara's
+/// l1-gas-price worker tracks: a base fee per unit of execution gas and,
+/// post-EIP-4844, a base fee per unit of blob gas. Only the latest prices
+/// are held, not a history across several blocks.
+#[derive(Debug, Clone, Default)]
+pub struct GasPriceHistory {
+    pub base_fee_per_gas: u128,
+    pub base_fee_per_blob_gas: u128,
+}
+
+impl GasPriceHistory {
+    /// The cheapest this transaction could plausibly cost, priced at the
+    /// latest known L1 gas prices rather than whatever prices were baked
+    /// into the estimate. Used only as a floor under the node's own
+    /// `overall_fee` (which already includes the data-gas cost post
+    /// EIP-4844) — never added on top of it, which would double-count
+    /// data availability.
+    fn min_fee(&self, gas_consumed: u128, blob_gas_consumed: u128) -> u128 {
+        self.base_fee_per_gas * gas_consumed + self.base_fee_per_blob_gas * blob_gas_consumed
+    }
+}
+
+/// Estimates `max_fee` for `call` sent from `account`, applying
+/// [`FEE_ESTIMATE_MULTIPLIER`] and flooring the result against
+/// `gas_history`'s L1 gas prices. Falls back to the static [`MAX_FEE`] if
+/// estimation fails.
+pub async fn estimate_max_fee(
+    account: &SingleOwnerAccount<std::sync::Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+    call: Call,
+    gas_history: &GasPriceHistory,
+) -> FieldElement {
+    match try_estimate_max_fee(account, call, gas_history).await {
+        Ok(max_fee) => max_fee,
+        Err(err) => {
+            warn!("Fee estimation unavailable ({err}), falling back to static max fee");
+            MAX_FEE
+        }
+    }
+}
+
+async fn try_estimate_max_fee(
+    account: &SingleOwnerAccount<std::sync::Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+    call: Call,
+    gas_history: &GasPriceHistory,
+) -> Result<FieldElement> {
+    let execution: ExecutionV1<_> = account
+        .execute(vec![call])
+        .block_id(BlockId::Tag(BlockTag::Pending));
+
+    let estimate = execution.estimate_fee().await?;
+
+    // `overall_fee` already prices in the transaction's data gas, so the
+    // L1 history is only used as a floor under it (for the case where
+    // gas prices have moved since the estimate was taken), never summed
+    // on top of it.
+    let scaled = (estimate.overall_fee as f64 * FEE_ESTIMATE_MULTIPLIER) as u128;
+    let floor = gas_history.min_fee(
+        estimate.gas_consumed as u128,
+        estimate.data_gas_consumed as u128,
+    );
+
+    Ok(FieldElement::from(scaled.max(floor)))
+}
+
+/// Pulls the L1 gas prices that feed blob-gas pricing: the base fee per
+/// gas and `base_fee_per_blob_gas`. Blob-gas usage (how full recent blobs
+/// were) is not collected.
+///
+/// Madara's l1-gas-price worker sources this from the L1 settlement
+/// layer's `eth_feeHistory`; here it's read from the `l1_gas_price` and
+/// `l1_data_gas_price` of the node's latest block header, since this tool
+/// only talks to the Starknet RPC.
+pub async fn fetch_gas_price_history(
+    provider: &JsonRpcClient<HttpTransport>,
+) -> Result<GasPriceHistory> {
+    let block = provider
+        .get_block_with_tx_hashes(BlockId::Tag(BlockTag::Latest))
+        .await?;
+
+    let (base_fee_per_gas, base_fee_per_blob_gas) = match block {
+        starknet::core::types::MaybePendingBlockWithTxHashes::Block(b) => (
+            felt_to_u128(b.l1_gas_price.price_in_wei),
+            felt_to_u128(b.l1_data_gas_price.price_in_wei),
+        ),
+        starknet::core::types::MaybePendingBlockWithTxHashes::PendingBlock(b) => (
+            felt_to_u128(b.l1_gas_price.price_in_wei),
+            felt_to_u128(b.l1_data_gas_price.price_in_wei),
+        ),
+    };
+
+    Ok(GasPriceHistory {
+        base_fee_per_gas,
+        base_fee_per_blob_gas,
+    })
+}
+
+fn felt_to_u128(felt: FieldElement) -> u128 {
+    let bytes = felt.to_bytes_be();
+    u128::from_be_bytes(bytes[16..32].try_into().expect("last 16 bytes of a felt"))
+}
@@ -1,8 +1,7 @@
 use std::{
     fs,
-    str::FromStr,
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::Instant,
 };
 
 use color_eyre::eyre::{bail, eyre};
@@ -12,10 +11,7 @@ use starknet::{
     contract::ContractFactory,
     core::{
         crypto::compute_hash_on_elements,
-        types::{
-            contract::legacy::LegacyContractClass, BlockId, BlockTag, ExecutionResult,
-            FieldElement, MaybePendingTransactionReceipt, StarknetError,
-        },
+        types::{BlockId, BlockTag, ExecutionResult, FieldElement, StarknetError},
     },
     macros::{felt, selector},
     providers::{
@@ -26,7 +22,24 @@ use starknet::{
 };
 use url::Url;
 
-pub static CHECK_INTERVAL: Duration = Duration::from_millis(500);
+mod accounts;
+mod confirm;
+mod contract;
+mod fees;
+mod metrics;
+mod nonce;
+mod scenario;
+
+use confirm::BackoffConfig;
+use contract::ContractArtifact;
+use fees::GasPriceHistory;
+use metrics::Metrics;
+use scenario::Scenario;
+
+/// Class hash of the OpenZeppelin account contract burner accounts are
+/// deployed as. Must already be declared on the target node.
+const BURNER_ACCOUNT_CLASS_HASH: FieldElement =
+    felt!("0x04c6d6cf894f8bc96bb9c525e6853e5483177841f7388f74a46cfda6f028c5d");
 
 const MAX_FEE: FieldElement = felt!("0x6efb28c75a0000");
 
@@ -38,12 +51,9 @@ async fn main() -> color_eyre::Result<()> {
     // Initialize the error handler.
     color_eyre::install()?;
 
-    let args: usize = std::env::args()
+    let scenario_path = std::env::args()
         .nth(1)
-        .as_deref()
-        .map(FromStr::from_str)
-        .transpose()?
-        .unwrap_or(1000);
+        .unwrap_or_else(|| "scenario.yaml".to_string());
 
     let starknet_rpc = Arc::new(JsonRpcClient::new(HttpTransport::new(Url::parse(
         "http://localhost:9944",
@@ -61,8 +71,12 @@ async fn main() -> color_eyre::Result<()> {
         ExecutionEncoding::New,
     );
 
-    let erc20_contract_artifact: LegacyContractClass =
-        serde_json::from_str(&fs::read_to_string("ERC20.json")?)?;
+    let erc20_artifact_path =
+        std::env::var("ERC20_ARTIFACT_PATH").unwrap_or_else(|_| "ERC20.json".to_string());
+    let erc20_casm_path = std::env::var("ERC20_CASM_PATH").ok();
+
+    let erc20_contract_artifact =
+        ContractArtifact::load(&erc20_artifact_path, erc20_casm_path.as_deref())?;
 
     let class_hash = erc20_contract_artifact.class_hash()?;
 
@@ -75,18 +89,21 @@ async fn main() -> color_eyre::Result<()> {
     } else {
         account.set_block_id(BlockId::Tag(BlockTag::Pending));
 
-        let tx_resp = account
-            .declare_legacy(Arc::new(erc20_contract_artifact))
-            .max_fee(MAX_FEE)
-            .nonce(nonce)
-            .send()
+        let outcome = erc20_contract_artifact
+            .declare(&account, nonce, MAX_FEE)
             .await?;
 
-        wait_for_tx(&starknet_rpc, tx_resp.transaction_hash, CHECK_INTERVAL).await?;
+        let confirmation = confirm::wait_for_tx(
+            &starknet_rpc,
+            outcome.transaction_hash,
+            BackoffConfig::default(),
+        )
+        .await?;
+        ensure_succeeded(outcome.transaction_hash, confirmation.result)?;
 
         nonce += FieldElement::ONE;
 
-        tx_resp.class_hash
+        outcome.class_hash
     };
 
     let contract_factory = ContractFactory::new(class_hash, &account);
@@ -127,7 +144,13 @@ async fn main() -> color_eyre::Result<()> {
         );
 
         let result = deploy.nonce(nonce).max_fee(MAX_FEE).send().await?;
-        wait_for_tx(&starknet_rpc, result.transaction_hash, CHECK_INTERVAL).await?;
+        let confirmation = confirm::wait_for_tx(
+            &starknet_rpc,
+            result.transaction_hash,
+            BackoffConfig::default(),
+        )
+        .await?;
+        ensure_succeeded(result.transaction_hash, confirmation.result)?;
 
         nonce += FieldElement::ONE;
 
@@ -156,23 +179,54 @@ async fn main() -> color_eyre::Result<()> {
         calldata: vec![VOID_ADDRESS, amount_low, amount_high],
     };
 
-    let mut vec = Vec::with_capacity(1000);
+    let scenario = Scenario::from_file(&scenario_path)?;
 
-    for _ in 0..args {
-        let result = account
-            .execute(vec![call.clone()])
-            .max_fee(MAX_FEE)
-            .nonce(nonce)
-            .send()
-            .await?;
+    info!(
+        "Loaded scenario with {} phase(s) from {scenario_path}",
+        scenario.phases.len()
+    );
 
-        vec.push(result.transaction_hash);
+    let metrics = Arc::new(Metrics::new());
+    let gas_history = Arc::new(fees::fetch_gas_price_history(&starknet_rpc).await.unwrap_or_else(|err| {
+        warn!("Could not fetch L1 gas price history ({err}), defaulting to zero blob fee");
+        GasPriceHistory::default()
+    }));
+
+    let burner_count: usize = std::env::var("BURNER_COUNT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    let pool = if burner_count > 0 {
+        let fund_amount = felt!("0xFFFFFFFF");
+        let burners = accounts::deploy_burner_accounts(
+            starknet_rpc.clone(),
+            &mut account,
+            address,
+            BURNER_ACCOUNT_CLASS_HASH,
+            FieldElement::from_byte_slice_be(b"SN_GOERLI")?,
+            burner_count,
+            fund_amount,
+        )
+        .await?;
+
+        info!("Deployed {burner_count} burner account(s) for load submission");
+
+        burners.into_iter().map(|b| Arc::new(b.account)).collect()
+    } else {
+        vec![Arc::new(account)]
+    };
 
-        nonce += FieldElement::ONE;
-    }
+    let run_start = Instant::now();
+    scenario::run_scenario(scenario, starknet_rpc, pool, call, metrics.clone(), gas_history).await?;
+    let run_end = Instant::now();
+
+    let summary = metrics.summarize(run_start, run_end);
+    summary.print();
 
-    for hash in vec {
-        wait_for_tx(&starknet_rpc, hash, CHECK_INTERVAL).await?;
+    if let Ok(json_path) = std::env::var("LOAD_SUMMARY_JSON") {
+        fs::write(&json_path, summary.to_json()?)?;
+        info!("Wrote run summary to {json_path}");
     }
 
     Ok(())
@@ -228,54 +282,13 @@ async fn check_already_declared(
     }
 }
 
-const WAIT_FOR_TX_TIMEOUT: Duration = Duration::from_secs(60);
-
-pub async fn wait_for_tx(
-    provider: &JsonRpcClient<HttpTransport>,
-    tx_hash: FieldElement,
-    check_interval: Duration,
-) -> color_eyre::Result<()> {
-    let start = SystemTime::now();
-
-    loop {
-        if start.elapsed().unwrap() >= WAIT_FOR_TX_TIMEOUT {
-            bail!("Timeout while waiting for transaction {tx_hash:#064x}");
-        }
-
-        match provider.get_transaction_receipt(tx_hash).await {
-            Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => {
-                match receipt.execution_result() {
-                    ExecutionResult::Succeeded => {
-                        return Ok(());
-                    }
-                    ExecutionResult::Reverted { reason } => {
-                        bail!(format!(
-                            "Transaction {tx_hash:#064x} has been rejected/reverted: {reason}"
-                        ));
-                    }
-                }
-            }
-            Ok(MaybePendingTransactionReceipt::PendingReceipt(pending)) => {
-                if let ExecutionResult::Reverted { reason } = pending.execution_result() {
-                    bail!(format!(
-                        "Transaction {tx_hash:#064x} has been rejected/reverted: {reason}"
-                    ));
-                }
-                debug!("Waiting for transaction {tx_hash:#064x} to be accepted");
-                tokio::time::sleep(check_interval).await;
-            }
-            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
-                code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
-                ..
-            })) => {
-                debug!("Waiting for transaction {tx_hash:#064x} to show up");
-                tokio::time::sleep(check_interval).await;
-            }
-            Err(err) => {
-                return Err(eyre!(err).wrap_err(format!(
-                    "Error while waiting for transaction {tx_hash:#064x}"
-                )))
-            }
+/// Bails if `result` is a revert; otherwise returns `Ok(())`. Used by the
+/// setup path (declare/deploy), where a revert should stop the run.
+fn ensure_succeeded(tx_hash: FieldElement, result: ExecutionResult) -> color_eyre::Result<()> {
+    match result {
+        ExecutionResult::Succeeded => Ok(()),
+        ExecutionResult::Reverted { reason } => {
+            bail!("Transaction {tx_hash:#064x} has been rejected/reverted: {reason}")
         }
     }
 }
@@ -0,0 +1,170 @@
+//! Throughput and latency statistics for a load run.
+//!
+//! Each virtual user timestamps its own transaction submissions and
+//! acceptances into a shared [`Metrics`] instance, which at the end of the
+//! run is reduced into a [`Summary`] of TPS and latency percentiles.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+/// Terminal outcome of a single submitted transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Succeeded,
+    Reverted,
+    /// Confirmation itself gave up on the transaction (timeout, or
+    /// exhausted transport retries) before its terminal state could be
+    /// observed.
+    Failed,
+}
+
+/// A single transaction's lifecycle timestamps, recorded by a virtual
+/// user. `accepted_at` is `None` for a transaction whose confirmation
+/// failed outright, since it never reached an observed terminal state.
+struct Sample {
+    submitted_at: Instant,
+    accepted_at: Option<Instant>,
+    outcome: Outcome,
+}
+
+/// Shared, lock-protected collector of per-transaction samples.
+///
+/// Cloned (via `Arc`) across virtual user tasks; every `record` call takes
+/// the lock only for the duration of a `Vec::push`.
+#[derive(Default)]
+pub struct Metrics {
+    samples: Mutex<Vec<Sample>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a transaction submitted at `submitted_at` with its terminal
+    /// `outcome`. `accepted_at` is the instant the transaction was
+    /// actually observed to finish, not the instant `record` happens to be
+    /// called — `None` if confirmation never reached a terminal state.
+    pub fn record(&self, submitted_at: Instant, accepted_at: Option<Instant>, outcome: Outcome) {
+        self.samples.lock().unwrap().push(Sample {
+            submitted_at,
+            accepted_at,
+            outcome,
+        });
+    }
+
+    /// Reduces the collected samples into a [`Summary`] covering the window
+    /// `[run_start, run_end]`.
+    pub fn summarize(&self, run_start: Instant, run_end: Instant) -> Summary {
+        let samples = self.samples.lock().unwrap();
+
+        let total = samples.len();
+        let succeeded = samples.iter().filter(|s| s.outcome == Outcome::Succeeded).count();
+        let reverted = samples.iter().filter(|s| s.outcome == Outcome::Reverted).count();
+        let failed = samples.iter().filter(|s| s.outcome == Outcome::Failed).count();
+
+        // Latency and accept-side throughput only cover samples that
+        // actually reached a terminal state; a failed confirmation has no
+        // real acceptance instant to measure either from.
+        let mut latencies: Vec<Duration> = samples
+            .iter()
+            .filter_map(|s| s.accepted_at.map(|a| a.duration_since(s.submitted_at)))
+            .collect();
+        latencies.sort_unstable();
+
+        let wall_clock = run_end.duration_since(run_start);
+
+        // Submit-side throughput is measured over the span between the
+        // first and last submission; accept-side throughput over the span
+        // between the first and last acceptance. These differ whenever
+        // confirmation lags behind submission, which is the point of
+        // tracking them separately.
+        let submit_tps = throughput(total, samples.iter().map(|s| s.submitted_at));
+        let accepted: Vec<Instant> = samples.iter().filter_map(|s| s.accepted_at).collect();
+        let accept_tps = throughput(accepted.len(), accepted.iter().copied());
+
+        Summary {
+            total_transactions: total,
+            succeeded,
+            reverted,
+            failed,
+            wall_clock_secs: wall_clock.as_secs_f64(),
+            submit_tps,
+            accept_tps,
+            p50_ms: percentile(&latencies, 0.50),
+            p95_ms: percentile(&latencies, 0.95),
+            p99_ms: percentile(&latencies, 0.99),
+            max_ms: latencies.last().map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0),
+        }
+    }
+}
+
+/// Transactions per second across the span of `timestamps`, i.e.
+/// `count / (max(timestamps) - min(timestamps))`. A span too short to
+/// measure (zero or one sample) reports zero rather than dividing by zero.
+fn throughput(count: usize, timestamps: impl Iterator<Item = Instant> + Clone) -> f64 {
+    let min = timestamps.clone().min();
+    let max = timestamps.max();
+
+    match (min, max) {
+        (Some(min), Some(max)) if max > min => count as f64 / (max - min).as_secs_f64(),
+        _ => 0.0,
+    }
+}
+
+/// Aggregate statistics for a completed load run.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub total_transactions: usize,
+    pub succeeded: usize,
+    pub reverted: usize,
+    pub failed: usize,
+    pub wall_clock_secs: f64,
+    pub submit_tps: f64,
+    pub accept_tps: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Summary {
+    /// Pretty-prints the summary to stdout.
+    pub fn print(&self) {
+        println!("--- Load run summary ---");
+        println!(
+            "transactions: {} ({} succeeded, {} reverted, {} failed to confirm)",
+            self.total_transactions, self.succeeded, self.reverted, self.failed
+        );
+        println!("wall clock: {:.2}s", self.wall_clock_secs);
+        println!(
+            "throughput: {:.2} submit tps, {:.2} accept tps",
+            self.submit_tps, self.accept_tps
+        );
+        println!(
+            "latency: p50={:.1}ms p95={:.1}ms p99={:.1}ms max={:.1}ms",
+            self.p50_ms, self.p95_ms, self.p99_ms, self.max_ms
+        );
+    }
+
+    /// Serializes the summary as JSON, e.g. for CI ingestion.
+    pub fn to_json(&self) -> color_eyre::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Computes the `p`-th percentile (0.0..=1.0) of an already-sorted slice of
+/// durations, in milliseconds.
+fn percentile(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+
+    sorted[rank].as_secs_f64() * 1000.0
+}
@@ -0,0 +1,124 @@
+//! A nonce manager sitting in front of a [`SingleOwnerAccount`], so that
+//! concurrent virtual users sharing one account don't race each other or go
+//! stale the moment a transaction fails or reverts.
+//!
+//! Borrows the "nonce-manager middleware" idea: nonces are handed out from
+//! an in-memory atomic counter seeded from the chain, and on a nonce
+//! mismatch from the sequencer the counter is resynced from `get_nonce()`
+//! before the call is replayed.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use color_eyre::eyre::Result;
+use log::warn;
+use starknet::{
+    accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount},
+    core::types::{FieldElement, StarknetError},
+    providers::{
+        jsonrpc::{HttpTransport, JsonRpcClient},
+        MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage,
+    },
+    signers::LocalWallet,
+};
+
+/// Maximum number of times a single call is replayed after a nonce-mismatch
+/// resync before giving up.
+const MAX_NONCE_RETRIES: u32 = 3;
+
+/// Hands out monotonically increasing nonces for one account, shared safely
+/// across concurrently-submitting virtual users.
+pub struct NonceManager {
+    account: Arc<SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>>,
+    next_nonce: AtomicU64,
+}
+
+impl NonceManager {
+    /// Seeds the manager from the account's on-chain nonce.
+    pub async fn new(
+        account: Arc<SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>>,
+    ) -> Result<Self> {
+        let nonce = account.get_nonce().await?;
+
+        Ok(Self {
+            account,
+            next_nonce: AtomicU64::new(felt_to_u64(nonce)),
+        })
+    }
+
+    /// The account this manager hands out nonces for, exposed so callers
+    /// can estimate fees against it before calling [`Self::execute`].
+    pub fn account(&self) -> &SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet> {
+        &self.account
+    }
+
+    /// Atomically reserves the next nonce to use.
+    fn reserve_nonce(&self) -> FieldElement {
+        FieldElement::from(self.next_nonce.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Re-fetches the on-chain nonce and folds it into the counter. Called
+    /// after a nonce-mismatch error so subsequent reservations recover.
+    ///
+    /// Uses `fetch_max` rather than `store`: other virtual users sharing
+    /// this manager may have already reserved nonces higher than the
+    /// on-chain value while their transactions are still in flight, and a
+    /// blind store would roll the counter back and re-hand-out those
+    /// pending nonces, cascading into further mismatches instead of
+    /// recovering from one.
+    async fn resync(&self) -> Result<()> {
+        let onchain = felt_to_u64(self.account.get_nonce().await?);
+        self.next_nonce.fetch_max(onchain, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Submits `call`, reserving a nonce for it, and transparently resyncs
+    /// and retries on a `StarknetError` nonce mismatch.
+    pub async fn execute(&self, call: Call, max_fee: FieldElement) -> Result<FieldElement> {
+        for attempt in 0..=MAX_NONCE_RETRIES {
+            let nonce = self.reserve_nonce();
+
+            let send_result = self
+                .account
+                .execute(vec![call.clone()])
+                .max_fee(max_fee)
+                .nonce(nonce)
+                .send()
+                .await;
+
+            match send_result {
+                Ok(result) => return Ok(result.transaction_hash),
+                Err(err) if is_nonce_mismatch(&err) && attempt < MAX_NONCE_RETRIES => {
+                    warn!("Nonce mismatch at {nonce}, resyncing and retrying (attempt {attempt})");
+                    self.resync().await?;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        unreachable!("loop above always returns within MAX_NONCE_RETRIES + 1 attempts")
+    }
+}
+
+fn is_nonce_mismatch(
+    err: &starknet::accounts::AccountError<
+        <SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet> as Account>::SignError,
+    >,
+) -> bool {
+    matches!(
+        err,
+        starknet::accounts::AccountError::Provider(ProviderError::StarknetError(
+            StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::InvalidTransactionNonce),
+                ..
+            }
+        ))
+    )
+}
+
+fn felt_to_u64(felt: FieldElement) -> u64 {
+    let bytes = felt.to_bytes_be();
+    u64::from_be_bytes(bytes[24..32].try_into().expect("last 8 bytes of a felt"))
+}
@@ -0,0 +1,192 @@
+//! Phase-based load generator, modeled after the scenario files used to
+//! drive load against Madara with artillery.
+//!
+//! A [`Scenario`] is an ordered list of [`Phase`]s. An arrival phase ramps up
+//! a number of virtual users over a `duration` window; a pause phase just
+//! waits. Every virtual user spawned by an arrival phase runs the scenario's
+//! `flow` to completion, looping the configured call `count` times.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::Result;
+use log::{debug, info, warn};
+use serde::Deserialize;
+use starknet::{
+    accounts::{Call, SingleOwnerAccount},
+    core::types::ExecutionResult,
+    providers::jsonrpc::{HttpTransport, JsonRpcClient},
+    signers::LocalWallet,
+};
+use tokio::task::JoinSet;
+
+use crate::{
+    confirm::{self, BackoffConfig, ConfirmationOutcome},
+    fees::{estimate_max_fee, GasPriceHistory},
+    metrics::{Metrics, Outcome},
+    nonce::NonceManager,
+};
+
+/// The inner loop a virtual user runs: call `executeERC20Transfer` `count`
+/// times back to back.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Flow {
+    pub count: usize,
+}
+
+/// One phase of a load profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Phase {
+    /// Spawn `arrival_count` virtual users evenly spaced over `duration`.
+    Arrival {
+        duration: u64,
+        #[serde(rename = "arrivalCount")]
+        arrival_count: u64,
+    },
+    /// Do nothing for `pause` seconds before moving to the next phase.
+    Pause { pause: u64 },
+}
+
+/// A full load profile: an ordered list of phases sharing one flow.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub phases: Vec<Phase>,
+    pub flow: Flow,
+}
+
+impl Scenario {
+    /// Loads a scenario from a YAML config file.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&raw)?)
+    }
+}
+
+/// Drives `scenario` against a pool of `accounts`, submitting `call` as the
+/// flow's transaction, honoring each phase's arrival pacing. Virtual users
+/// are distributed round-robin across the pool; each account keeps its own
+/// nonce stream, shared by the virtual users assigned to it, letting
+/// independent accounts submit concurrently.
+pub async fn run_scenario(
+    scenario: Scenario,
+    starknet_rpc: Arc<JsonRpcClient<HttpTransport>>,
+    accounts: Vec<Arc<SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>>>,
+    call: Call,
+    metrics: Arc<Metrics>,
+    gas_history: Arc<GasPriceHistory>,
+) -> Result<()> {
+    let mut nonce_managers = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        nonce_managers.push(Arc::new(NonceManager::new(account).await?));
+    }
+
+    let mut virtual_users = JoinSet::new();
+
+    for phase in scenario.phases {
+        match phase {
+            Phase::Pause { pause } => {
+                info!("Phase: pausing for {pause}s");
+                tokio::time::sleep(Duration::from_secs(pause)).await;
+            }
+            Phase::Arrival {
+                duration,
+                arrival_count,
+            } => {
+                info!("Phase: ramping up {arrival_count} virtual users over {duration}s");
+                let spacing = Duration::from_secs(duration) / arrival_count.max(1) as u32;
+
+                for i in 0..arrival_count {
+                    let nonce_manager = nonce_managers[i as usize % nonce_managers.len()].clone();
+                    let starknet_rpc = starknet_rpc.clone();
+                    let call = call.clone();
+                    let metrics = metrics.clone();
+                    let gas_history = gas_history.clone();
+                    let count = scenario.flow.count;
+
+                    virtual_users.spawn(async move {
+                        debug!("Virtual user {i} starting {count} transfers");
+                        execute_erc20_transfer_flow(
+                            starknet_rpc,
+                            nonce_manager,
+                            call,
+                            count,
+                            metrics,
+                            gas_history,
+                        )
+                        .await
+                    });
+
+                    tokio::time::sleep(spacing).await;
+                }
+            }
+        }
+    }
+
+    while let Some(result) = virtual_users.join_next().await {
+        result??;
+    }
+
+    Ok(())
+}
+
+/// Runs one virtual user's flow: submits `call` `count` times back to
+/// back, then confirms the whole batch concurrently. Nonces come from the
+/// shared `nonce_manager`, so many virtual users can drive the same
+/// account concurrently without racing each other's nonce, and the
+/// confirmation phase scales with the submission phase instead of waiting
+/// on each transaction one at a time.
+async fn execute_erc20_transfer_flow(
+    starknet_rpc: Arc<JsonRpcClient<HttpTransport>>,
+    nonce_manager: Arc<NonceManager>,
+    call: Call,
+    count: usize,
+    metrics: Arc<Metrics>,
+    gas_history: Arc<GasPriceHistory>,
+) -> Result<()> {
+    let mut submissions = Vec::with_capacity(count);
+
+    // Every call in this flow is identical, so the fee estimate is too:
+    // estimate once up front instead of paying a full `estimate_fee`
+    // round-trip before each of `count` sends, which would otherwise
+    // roughly halve achievable submit throughput.
+    let max_fee = estimate_max_fee(nonce_manager.account(), call.clone(), &gas_history).await;
+
+    for _ in 0..count {
+        let submitted_at = Instant::now();
+        let tx_hash = nonce_manager.execute(call.clone(), max_fee).await?;
+
+        submissions.push((tx_hash, submitted_at));
+    }
+
+    let tx_hashes = submissions.iter().map(|(hash, _)| *hash).collect();
+
+    let confirmations = confirm::wait_for_many(
+        &starknet_rpc,
+        tx_hashes,
+        BackoffConfig::default(),
+        count.max(1),
+    )
+    .await;
+
+    for ((_, submitted_at), outcome) in submissions.into_iter().zip(confirmations) {
+        match outcome {
+            ConfirmationOutcome::Reached(confirmation) => {
+                let outcome = if matches!(confirmation.result, ExecutionResult::Reverted { .. }) {
+                    Outcome::Reverted
+                } else {
+                    Outcome::Succeeded
+                };
+                metrics.record(submitted_at, Some(confirmation.accepted_at), outcome);
+            }
+            ConfirmationOutcome::Failed { tx_hash, error } => {
+                warn!("Transaction {tx_hash:#064x} failed to confirm: {error}");
+                metrics.record(submitted_at, None, Outcome::Failed);
+            }
+        }
+    }
+
+    Ok(())
+}